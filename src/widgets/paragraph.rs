@@ -1,12 +1,90 @@
-use either::Either;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::buffer::Buffer;
 use crate::layout::{Alignment, Rect, ScrollFrom};
 use crate::style::Style;
-use crate::widgets::reflow::{LineComposer, LineTruncator, Styled, WordWrapper};
-use crate::widgets::{Block, Text, Widget};
+use crate::widgets::reflow::{CharacterWrapper, LineComposer, LineTruncator, Styled, WordWrapper};
+use crate::widgets::{Block, StatefulWidget, Text, Widget};
+
+/// A two-dimensional scroll offset, in cells: `x` columns from the left, `y` lines from the top
+/// (or bottom, depending on [`ScrollFrom`]) of the composed text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ScrollPos {
+    /// Number of columns to shift the rendered window right
+    pub x: u16,
+    /// Number of lines to shift the rendered window down
+    pub y: u16,
+}
+
+impl ScrollPos {
+    pub fn new(x: u16, y: u16) -> ScrollPos {
+        ScrollPos { x, y }
+    }
+}
+
+impl From<u16> for ScrollPos {
+    /// Backwards-compatible conversion from a plain line offset, with no horizontal pan.
+    fn from(y: u16) -> ScrollPos {
+        ScrollPos { x: 0, y }
+    }
+}
+
+/// State to be used with a stateful [`Paragraph`] so that a host application can read back how
+/// the text was laid out on the last `draw` call, e.g. to size a companion scrollbar or clamp
+/// `scroll` to `lines.saturating_sub(height)`. Also holds the reflow cache (see
+/// [`Paragraph::text_version`]) across frames.
+#[derive(Debug, Clone, Default)]
+pub struct ParagraphState {
+    /// Scroll offset that was used to produce this layout
+    pub scroll: ScrollPos,
+    /// Total number of lines produced by wrapping/truncating the text
+    pub lines: u16,
+    /// Height of the text area the paragraph was rendered into
+    pub height: u16,
+    cache: Option<ReflowCache>,
+}
+
+/// A single source-text grapheme, flattened out of the `Text` items passed to [`Paragraph::new`]
+/// and kept owned so it can outlive the borrow that produced it.
+#[derive(Debug, Clone)]
+struct CachedGrapheme {
+    symbol: String,
+    style: Style,
+    /// Index of the `\n`-delimited source line this grapheme came from
+    source_line: usize,
+}
+
+/// A single wrapped row, as a `(start, len)` window into `ReflowCache::graphemes`.
+#[derive(Debug, Clone, Copy)]
+struct WrappedLine {
+    /// Index of the source line (see [`CachedGrapheme::source_line`]) this row was wrapped from
+    source_line: usize,
+    /// Index of this row's first grapheme in `ReflowCache::graphemes`
+    start_grapheme: usize,
+    /// Number of graphemes this row covers
+    len: usize,
+    /// Rendered width of this row, in columns
+    width: u16,
+}
+
+/// Cached reflow output, kept in [`ParagraphState`] across frames. Valid only for the
+/// `text_version`/`width`/`wrap`/`line_numbers`/`style` it was computed for — `style` is part of
+/// the key because it's baked into each `Text::Raw` grapheme's `CachedGrapheme::style` at reflow
+/// time, so a `style`-only change wouldn't otherwise be visible to the freshness check.
+#[derive(Debug, Clone)]
+struct ReflowCache {
+    text_version: u64,
+    width: u16,
+    wrap: Option<WrapMethod>,
+    line_numbers: bool,
+    style: Style,
+    /// Columns reserved on the left for the line-number gutter, already subtracted from `width`
+    /// when reflowing
+    gutter_width: u16,
+    graphemes: Vec<CachedGrapheme>,
+    wrapped_lines: Vec<WrappedLine>,
+}
 
 fn get_line_offset(line_width: u16, text_area_width: u16, alignment: Alignment) -> u16 {
     match alignment {
@@ -16,12 +94,36 @@ fn get_line_offset(line_width: u16, text_area_width: u16, alignment: Alignment)
     }
 }
 
+/// Number of base-10 digits needed to print `n` (at least 1).
+fn digit_count(mut n: usize) -> u16 {
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+/// How a [`Paragraph`] breaks lines that don't fit `text_area`'s width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMethod {
+    /// Break on word boundaries, like `WordWrapper` always did. When `trim` is set, leading
+    /// whitespace on a continuation row (one produced by wrapping, not a `\n` in the source) is
+    /// discarded instead of rendered. A token wider than the text area is force-split at the
+    /// column boundary rather than silently dropped.
+    Word {
+        trim: bool,
+    },
+    /// Break at the exact column, regardless of word boundaries.
+    Character,
+}
+
 /// A widget to display some text.
 ///
 /// # Examples
 ///
 /// ```
-/// # use tui::widgets::{Block, Borders, Paragraph, Text};
+/// # use tui::widgets::{Block, Borders, Paragraph, Text, WrapMethod};
 /// # use tui::style::{Style, Color};
 /// # use tui::layout::{Alignment};
 /// # fn main() {
@@ -33,9 +135,13 @@ fn get_line_offset(line_width: u16, text_area_width: u16, alignment: Alignment)
 ///     .block(Block::default().title("Paragraph").borders(Borders::ALL))
 ///     .style(Style::default().fg(Color::White).bg(Color::Black))
 ///     .alignment(Alignment::Center)
-///     .wrap(true);
+///     .wrap(Some(WrapMethod::Word { trim: true }));
 /// # }
 /// ```
+///
+/// `Paragraph` also implements [`StatefulWidget`], which writes the post-wrap line count and the
+/// height of the rendered text area into a [`ParagraphState`] so callers can size a scrollbar or
+/// clamp `scroll` to `state.lines.saturating_sub(state.height)`.
 pub struct Paragraph<'a, 't, T>
 where
     T: Iterator<Item = &'t Text<'t>>,
@@ -44,19 +150,25 @@ where
     block: Option<Block<'a>>,
     /// Widget style
     style: Style,
-    /// Wrap the text or not
-    wrapping: bool,
+    /// How to wrap the text, if at all
+    wrap: Option<WrapMethod>,
     /// The text to display
     text: T,
     /// Should we parse the text for embedded commands
     raw: bool,
-    /// Scroll offset (in number of lines)
-    scroll: u16,
+    /// Scroll offset
+    scroll: ScrollPos,
     /// Indicates if scroll offset starts from top or bottom of content
     scroll_from: ScrollFrom,
     scroll_overflow_char: Option<char>,
     /// Aligenment of the text
     alignment: Alignment,
+    /// Generation counter used to invalidate the reflow cache held in `ParagraphState`
+    text_version: u64,
+    /// Print a right-aligned, 1-based line number gutter before each source line
+    line_numbers: bool,
+    /// Style applied to the line-number gutter
+    line_number_style: Style,
 }
 
 impl<'a, 't, T> Paragraph<'a, 't, T>
@@ -67,13 +179,16 @@ where
         Paragraph {
             block: None,
             style: Default::default(),
-            wrapping: false,
+            wrap: None,
             raw: false,
             text,
-            scroll: 0,
+            scroll: ScrollPos::default(),
             scroll_from: ScrollFrom::Top,
             scroll_overflow_char: None,
             alignment: Alignment::Left,
+            text_version: 0,
+            line_numbers: false,
+            line_number_style: Default::default(),
         }
     }
 
@@ -87,8 +202,10 @@ where
         self
     }
 
-    pub fn wrap(mut self, flag: bool) -> Paragraph<'a, 't, T> {
-        self.wrapping = flag;
+    /// Sets how to wrap lines that don't fit `text_area`'s width, or `None` to truncate them
+    /// instead.
+    pub fn wrap(mut self, method: Option<WrapMethod>) -> Paragraph<'a, 't, T> {
+        self.wrap = method;
         self
     }
 
@@ -97,8 +214,11 @@ where
         self
     }
 
-    pub fn scroll(mut self, offset: u16) -> Paragraph<'a, 't, T> {
-        self.scroll = offset;
+    pub fn scroll<S>(mut self, offset: S) -> Paragraph<'a, 't, T>
+    where
+        S: Into<ScrollPos>,
+    {
+        self.scroll = offset.into();
         self
     }
 
@@ -119,13 +239,46 @@ where
         self.alignment = alignment;
         self
     }
+
+    /// Sets the generation counter used to invalidate the reflow cache kept in
+    /// [`ParagraphState`] across frames. Defaults to `0`, which is reserved to mean "not opted
+    /// in" and always forces a reflow — so a caller that never calls this still renders
+    /// correctly on every frame, just without the caching benefit.
+    ///
+    /// To opt in, pass any non-zero value that changes whenever the text passed to `new` has
+    /// actually changed; `draw` then only re-wraps when `text_version`, the text area's width,
+    /// [`wrap`](Paragraph::wrap), [`line_numbers`](Paragraph::line_numbers) or
+    /// [`style`](Paragraph::style) changes, and otherwise slices the cached layout from the
+    /// previous frame. Forgetting to bump it after an edit is still a bug (the redraw will show
+    /// stale content), but it can never silently freeze the widget the way leaving it at `0`
+    /// would have.
+    pub fn text_version(mut self, text_version: u64) -> Paragraph<'a, 't, T> {
+        self.text_version = text_version;
+        self
+    }
+
+    /// Enables a left-hand gutter printing the 1-based source line number before each line,
+    /// sized to fit the last line's digit count. Continuation rows produced by wrapping a single
+    /// source line leave the gutter blank rather than repeating the number.
+    pub fn line_numbers(mut self, flag: bool) -> Paragraph<'a, 't, T> {
+        self.line_numbers = flag;
+        self
+    }
+
+    /// Sets the style used to paint the line-number gutter (see [`Paragraph::line_numbers`]).
+    pub fn line_number_style(mut self, style: Style) -> Paragraph<'a, 't, T> {
+        self.line_number_style = style;
+        self
+    }
 }
 
-impl<'a, 't, 'b, T> Widget for Paragraph<'a, 't, T>
+impl<'a, 't, 'b, T> StatefulWidget for Paragraph<'a, 't, T>
 where
     T: Iterator<Item = &'t Text<'t>>,
 {
-    fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+    type State = ParagraphState;
+
+    fn draw(&mut self, area: Rect, buf: &mut Buffer, state: &mut ParagraphState) {
         let text_area = match self.block {
             Some(ref mut b) => {
                 b.draw(area, buf);
@@ -134,115 +287,585 @@ where
             None => area,
         };
 
+        state.height = text_area.height;
+
         if text_area.height < 1 {
             return;
         }
 
         self.background(text_area, buf, self.style.bg);
 
-        let style = self.style;
+        // text_version == 0 means the caller hasn't opted into caching; always reflow rather
+        // than risk serving a stale layout forever (see `Paragraph::text_version`)
+        let cache_is_fresh = self.text_version != 0
+            && state.cache.as_ref().map_or(false, |cache| {
+                cache.text_version == self.text_version
+                    && cache.width == text_area.width
+                    && cache.wrap == self.wrap
+                    && cache.line_numbers == self.line_numbers
+                    && cache.style == self.style
+            });
+        if !cache_is_fresh {
+            state.cache = Some(self.reflow(text_area.width));
+        }
+        let cache = state.cache.as_ref().expect("reflow cache just populated");
+        let num_lines = cache.wrapped_lines.len() as u16;
 
-        let mut styled = self.text.by_ref().flat_map(|t| match *t {
-            Text::Raw(ref d) => {
-                let data: &'t str = d; // coerce to &str
-                Either::Left(UnicodeSegmentation::graphemes(data, true).map(|g| Styled(g, style)))
-            }
-            Text::Styled(ref d, s) => {
-                let data: &'t str = d; // coerce to &str
-                Either::Right(UnicodeSegmentation::graphemes(data, true).map(move |g| Styled(g, s)))
-            }
-        });
+        let first_line_index = match self.scroll_from {
+            ScrollFrom::Top => self.scroll.y as i16,
+            ScrollFrom::Bottom => match self.scroll_overflow_char {
+                // if scroll_overflow is not set, don't
+                // ever scroll beyond the bounds of the content
+                None => {
+                    if num_lines <= text_area.height + self.scroll.y {
+                        // prevents us from scrolling up past the
+                        // first line, or from scrolling at all
+                        // if num_lines <= text_area.height
+                        0
+                    } else {
+                        // default ScrollFrom::Bottom behavior,
+                        // scroll == 0 floats content to bottom,
+                        // scroll > 0 scrolling up, back in history
+                        (num_lines - (text_area.height + self.scroll.y)) as i16
+                    }
+                }
+                // if scroll_overflow is set, scrolling up
+                // back in history past the top of the content results
+                // in a repeated character on each subsequent line
+                // (scroll_overflow_char)
+                Some(_) => {
+                    if num_lines <= text_area.height {
+                        // if content doesn't fill the text_area,
+                        // scrolling should be reverse of normal
+                        // behavior
+                        -(self.scroll.y as i16)
+                    } else {
+                        // default ScrollFrom::Bottom behavior,
+                        // scroll == 0 floats content to bottom,
+                        // scroll > 0 scrolling up, back in history
+                        num_lines as i16 - (text_area.height + self.scroll.y) as i16
+                    }
+                }
+            },
+        };
 
-        let mut line_composer: Box<dyn LineComposer> = if self.wrapping {
-            Box::new(WordWrapper::new(&mut styled, text_area.width))
+        let gutter_width = cache.gutter_width;
+        let content_width = text_area.width.saturating_sub(gutter_width);
+        // seed from the row just above the first visible one so a viewport that opens mid-wrap
+        // (first visible row is a continuation) still leaves that row's gutter blank
+        let mut previous_source_line: Option<usize> = if first_line_index > 0 {
+            cache
+                .wrapped_lines
+                .get(first_line_index as usize - 1)
+                .map(|line| line.source_line)
         } else {
-            Box::new(LineTruncator::new(&mut styled, text_area.width))
+            None
         };
 
-        let (first_line_index, mut get_next_line): (
-            i16,
-            Box<dyn FnMut() -> Option<(Vec<Styled<'t>>, u16)>>,
-        ) = match self.scroll_from {
-            ScrollFrom::Top => {
-                let get_next_line = Box::new(|| {
-                    line_composer
-                        .next_line()
-                        .map(|(line, line_width)| (line.to_vec(), line_width))
-                });
+        for y in 0..text_area.height {
+            if (y as i16) < -first_line_index {
+                let overflow_char = self.scroll_overflow_char.unwrap();
+                buf.get_mut(text_area.left() + gutter_width, text_area.top() + y as u16)
+                    .set_symbol(&overflow_char.to_string());
+                continue;
+            }
 
-                (self.scroll as i16, get_next_line)
+            let line_index = first_line_index + y as i16;
+            if line_index < 0 {
+                continue;
             }
-            ScrollFrom::Bottom => {
-                let all_lines = line_composer.collect_lines();
-                let num_lines = all_lines.len() as u16;
-                let scroll_offset = match self.scroll_overflow_char {
-                    // if scroll_overflow is not set, don't
-                    // ever scroll beyond the bounds of the content
-                    None => {
-                        if num_lines <= text_area.height + self.scroll {
-                            // prevents us from scrolling up past the
-                            // first line, or from scrolling at all
-                            // if num_lines <= text_area.height
-                            0
-                        } else {
-                            // default ScrollFrom::Bottom behavior,
-                            // scroll == 0 floats content to bottom,
-                            // scroll > 0 scrolling up, back in history
-                            (num_lines - (text_area.height + self.scroll)) as i16
-                        }
-                    }
-                    // if scroll_overflow is set, scrolling up
-                    // back in history past the top of the content results
-                    // in a repeated character on each subsequent line
-                    // (scroll_overflow_char)
-                    Some(_) => {
-                        if num_lines <= text_area.height {
-                            // if content doesn't fill the text_area,
-                            // scrolling should be reverse of normal
-                            // behavior
-                            -(self.scroll as i16)
-                        } else {
-                            // default ScrollFrom::Bottom behavior,
-                            // scroll == 0 floats content to bottom,
-                            // scroll > 0 scrolling up, back in history
-                            num_lines as i16 - (text_area.height + self.scroll) as i16
-                        }
-                    }
-                };
+            let wrapped_line = match cache.wrapped_lines.get(line_index as usize) {
+                Some(wrapped_line) => wrapped_line,
+                None => break,
+            };
+
+            if gutter_width > 0 {
+                let is_continuation = previous_source_line == Some(wrapped_line.source_line);
+                if !is_continuation {
+                    let label = (wrapped_line.source_line + 1).to_string();
+                    let label_x = gutter_width.saturating_sub(1 + label.width() as u16);
+                    buf.set_string(
+                        text_area.left() + label_x,
+                        text_area.top() + y,
+                        &label,
+                        self.line_number_style,
+                    );
+                }
+                previous_source_line = Some(wrapped_line.source_line);
+            }
+
+            let line_offset = get_line_offset(wrapped_line.width, content_width, self.alignment);
+            // pan the aligned line left by the horizontal scroll offset; a grapheme that would
+            // straddle the left edge is dropped rather than drawn half off-screen, leaving the
+            // background showing through instead
+            let mut x = line_offset as i32 - self.scroll.x as i32;
 
-                let mut all_lines_iter = all_lines.into_iter();
-                let get_next_line = Box::new(move || all_lines_iter.next());
+            let graphemes = &cache.graphemes
+                [wrapped_line.start_grapheme..wrapped_line.start_grapheme + wrapped_line.len];
+            for grapheme in graphemes {
+                let symbol_width = grapheme.symbol.width() as i32;
+                if x >= 0 && (x as u16) < content_width {
+                    buf.get_mut(
+                        text_area.left() + gutter_width + x as u16,
+                        text_area.top() + y,
+                    )
+                    .set_symbol(&grapheme.symbol)
+                    .set_style(grapheme.style);
+                } else if x >= content_width as i32 {
+                    break;
+                }
+                x += symbol_width;
+            }
+        }
+
+        state.scroll = self.scroll;
+        state.lines = num_lines;
+    }
+}
 
-                (scroll_offset, get_next_line)
+impl<'a, 't, T> Paragraph<'a, 't, T>
+where
+    T: Iterator<Item = &'t Text<'t>>,
+{
+    /// Flattens the remaining text into owned graphemes and reflows it at `width`, producing a
+    /// fresh [`ReflowCache`]. This is the O(total text) pass that caching in `draw` lets a
+    /// scroll-only redraw skip.
+    ///
+    /// Row widths are taken as reported by the composer, which never splits a grapheme across
+    /// the text area's right edge: a width-2 grapheme that would land with only one column left
+    /// is held back and moved to the start of the next row (dropped instead, in non-wrapping
+    /// mode).
+    fn reflow(&mut self, width: u16) -> ReflowCache {
+        let mut graphemes = Vec::new();
+        let mut source_line = 0usize;
+        for t in self.text.by_ref() {
+            let (data, style): (&str, Style) = match *t {
+                Text::Raw(ref d) => (d, self.style),
+                Text::Styled(ref d, s) => (d, s),
+            };
+            for g in UnicodeSegmentation::graphemes(data, true) {
+                if g == "\n" {
+                    source_line += 1;
+                }
+                graphemes.push(CachedGrapheme {
+                    symbol: g.to_string(),
+                    style,
+                    source_line,
+                });
             }
+        }
+
+        // reserve a gutter column sized to the last source line's digit count; it must come out
+        // of the width handed to the composer so wrapping still respects the visible text column
+        let gutter_width = if self.line_numbers {
+            let last_source_line = graphemes.last().map_or(0, |g| g.source_line);
+            digit_count(last_source_line + 1) + 1
+        } else {
+            0
         };
+        let content_width = width.saturating_sub(gutter_width);
 
-        let mut current_line_index = 0;
+        let wrapped_lines = compose_wrapped_lines(&graphemes, content_width, self.wrap);
 
-        for y in 0..text_area.height {
-            if (y as i16) < -first_line_index {
-                let overflow_char = self.scroll_overflow_char.unwrap();
-                buf.get_mut(text_area.left(), text_area.top() + y as u16)
-                    .set_symbol(&overflow_char.to_string());
-            } else {
-                while let Some((current_line, current_line_width)) = get_next_line() {
-                    if current_line_index >= first_line_index {
-                        let mut x =
-                            get_line_offset(current_line_width, text_area.width, self.alignment);
-
-                        for Styled(symbol, style) in current_line {
-                            buf.get_mut(text_area.left() + x, text_area.top() + y)
-                                .set_symbol(symbol)
-                                .set_style(style);
-                            x += symbol.width() as u16;
-                        }
-                        current_line_index += 1;
-                        break;
-                    } else {
-                        current_line_index += 1;
-                    }
+        ReflowCache {
+            text_version: self.text_version,
+            width,
+            wrap: self.wrap,
+            line_numbers: self.line_numbers,
+            style: self.style,
+            gutter_width,
+            graphemes,
+            wrapped_lines,
+        }
+    }
+}
+
+/// Drives the `LineComposer` selected by `wrap` over `graphemes` and translates each returned
+/// row back into a [`WrappedLine`] window. Word/character breaking, trim and force-split all
+/// live in the composer (`reflow.rs`); this function's only job is bookkeeping.
+///
+/// Relies on a contract with `reflow.rs`: a composer is fed `&str`s borrowed straight out of
+/// `graphemes` and is expected to hand the same slices back unchanged (never a copy or a
+/// merged/re-split grapheme), so the rows it returns can be matched back to `graphemes` by
+/// comparing `str` pointers instead of content. See the `ptr::eq` scan below.
+fn compose_wrapped_lines(
+    graphemes: &[CachedGrapheme],
+    content_width: u16,
+    wrap: Option<WrapMethod>,
+) -> Vec<WrappedLine> {
+    let mut styled_iter = graphemes.iter().map(|g| Styled(g.symbol.as_str(), g.style));
+    let mut composer: Box<dyn LineComposer<'_> + '_> = match wrap {
+        Some(WrapMethod::Character) => {
+            Box::new(CharacterWrapper::new(&mut styled_iter, content_width))
+        }
+        Some(WrapMethod::Word { trim }) => {
+            Box::new(WordWrapper::new(&mut styled_iter, content_width, trim))
+        }
+        None => Box::new(LineTruncator::new(&mut styled_iter, content_width)),
+    };
+
+    let mut wrapped_lines = Vec::new();
+    let mut cursor = 0usize;
+    while let Some((line, line_width)) = composer.next_line() {
+        let start_grapheme = match line.first() {
+            Some(Styled(first_symbol, _)) => {
+                let scan_start = cursor;
+                while cursor < graphemes.len()
+                    && !std::ptr::eq(
+                        graphemes[cursor].symbol.as_str().as_ptr(),
+                        first_symbol.as_ptr(),
+                    )
+                {
+                    cursor += 1;
+                }
+                if cursor == graphemes.len() {
+                    // the scan ran dry: the composer handed back a grapheme we can't find by
+                    // pointer, meaning the ptr::eq contract with reflow.rs above no longer
+                    // holds. Rewind instead of silently treating the remaining text as one
+                    // unbounded row.
+                    debug_assert!(
+                        false,
+                        "line composer returned a grapheme not present in the cached slice by \
+                         pointer identity — reflow.rs may have copied or re-split it"
+                    );
+                    cursor = scan_start;
+                    scan_start
+                } else {
+                    cursor
                 }
             }
+            // an empty row (a blank source line, or a wrap-trimmed remainder) doesn't advance
+            // the cursor itself; the next non-empty row's scan skips over whatever separator
+            // graphemes (e.g. `'\n'`) sit in between
+            None => cursor,
+        };
+        if !line.is_empty() {
+            cursor = start_grapheme + line.len();
+        }
+
+        let source_line = graphemes
+            .get(start_grapheme)
+            .map(|g| g.source_line)
+            .unwrap_or(0);
+        wrapped_lines.push(WrappedLine {
+            source_line,
+            start_grapheme,
+            len: line.len(),
+            width: line_width,
+        });
+    }
+
+    wrapped_lines
+}
+
+impl<'a, 't, 'b, T> Widget for Paragraph<'a, 't, T>
+where
+    T: Iterator<Item = &'t Text<'t>>,
+{
+    fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+        let mut state = ParagraphState::default();
+        StatefulWidget::draw(self, area, buf, &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+
+    #[test]
+    fn gutter_blank_on_continuation_row_after_scroll() {
+        let text = [Text::raw(
+            "one two three four five six seven eight nine ten eleven twelve",
+        )];
+        let area = Rect::new(0, 0, 10, 3);
+        let mut buf = Buffer::empty(area);
+        let mut state = ParagraphState::default();
+        StatefulWidget::draw(
+            &mut Paragraph::new(text.iter())
+                .line_numbers(true)
+                .wrap(Some(WrapMethod::Word { trim: true }))
+                .scroll(ScrollPos::new(0, 1)),
+            area,
+            &mut buf,
+            &mut state,
+        );
+
+        // the first visible row is a continuation of the single source line (scrolled past the
+        // row that carried the "1"), so its gutter column must stay blank rather than repeating
+        // or incrementing the number
+        assert_eq!(buf.get(0, 0).symbol, " ");
+    }
+
+    #[test]
+    fn gutter_shows_number_on_first_row_without_scroll() {
+        let text = [Text::raw(
+            "one two three four five six seven eight nine ten eleven twelve",
+        )];
+        let area = Rect::new(0, 0, 10, 3);
+        let mut buf = Buffer::empty(area);
+        let mut state = ParagraphState::default();
+        StatefulWidget::draw(
+            &mut Paragraph::new(text.iter())
+                .line_numbers(true)
+                .wrap(Some(WrapMethod::Word { trim: true })),
+            area,
+            &mut buf,
+            &mut state,
+        );
+
+        assert_eq!(buf.get(0, 0).symbol, "1");
+    }
+
+    #[test]
+    fn cache_is_reused_across_frames_with_same_text_version() {
+        let text = [Text::raw("hello world")];
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+        let mut state = ParagraphState::default();
+
+        StatefulWidget::draw(
+            &mut Paragraph::new(text.iter()).text_version(1),
+            area,
+            &mut buf,
+            &mut state,
+        );
+        let first_cache_graphemes = state.cache.as_ref().unwrap().graphemes.len();
+
+        // same text_version, width and wrap as before: draw should slice the existing cache
+        // rather than re-flowing, so an empty iterator is still rendered correctly
+        let empty: [Text; 0] = [];
+        StatefulWidget::draw(
+            &mut Paragraph::new(empty.iter()).text_version(1),
+            area,
+            &mut buf,
+            &mut state,
+        );
+        assert_eq!(
+            state.cache.as_ref().unwrap().graphemes.len(),
+            first_cache_graphemes
+        );
+    }
+
+    #[test]
+    fn cache_is_invalidated_when_text_version_is_zero() {
+        let text = [Text::raw("hello world")];
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+        let mut state = ParagraphState::default();
+
+        // text_version defaults to 0, i.e. "not opted in": every draw must reflow, so a second
+        // draw over an emptied iterator picks up the change instead of reusing stale graphemes
+        StatefulWidget::draw(&mut Paragraph::new(text.iter()), area, &mut buf, &mut state);
+        assert!(state.cache.as_ref().unwrap().graphemes.len() > 0);
+
+        let empty: [Text; 0] = [];
+        StatefulWidget::draw(
+            &mut Paragraph::new(empty.iter()),
+            area,
+            &mut buf,
+            &mut state,
+        );
+        assert_eq!(state.cache.as_ref().unwrap().graphemes.len(), 0);
+    }
+
+    #[test]
+    fn cache_is_invalidated_when_style_changes_under_the_same_text_version() {
+        use crate::style::Color;
+
+        let text = [Text::raw("hi")];
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+        let mut state = ParagraphState::default();
+
+        StatefulWidget::draw(
+            &mut Paragraph::new(text.iter())
+                .text_version(1)
+                .style(Style::default().fg(Color::Red)),
+            area,
+            &mut buf,
+            &mut state,
+        );
+        assert_eq!(
+            state.cache.as_ref().unwrap().graphemes[0].style,
+            Style::default().fg(Color::Red)
+        );
+
+        // same text_version, but a different style: Text::Raw bakes style into CachedGrapheme at
+        // reflow time, so this must still reflow instead of serving the previous frame's colors
+        StatefulWidget::draw(
+            &mut Paragraph::new(text.iter())
+                .text_version(1)
+                .style(Style::default().fg(Color::Blue)),
+            area,
+            &mut buf,
+            &mut state,
+        );
+        assert_eq!(
+            state.cache.as_ref().unwrap().graphemes[0].style,
+            Style::default().fg(Color::Blue)
+        );
+    }
+
+    #[test]
+    fn draw_reports_wrapped_line_count_and_text_area_height_in_state() {
+        // "abcde fghij" wraps to exactly two 5-wide rows: "abcde" then "fghij"
+        let text = [Text::raw("abcde fghij")];
+        let area = Rect::new(0, 0, 5, 3);
+        let mut buf = Buffer::empty(area);
+        let mut state = ParagraphState::default();
+
+        StatefulWidget::draw(
+            &mut Paragraph::new(text.iter()).wrap(Some(WrapMethod::Word { trim: true })),
+            area,
+            &mut buf,
+            &mut state,
+        );
+
+        assert_eq!(state.lines, 2);
+        assert_eq!(state.height, 3);
+    }
+
+    #[test]
+    fn scroll_x_pans_the_rendered_line_left() {
+        let text = [Text::raw("abcdef")];
+        let area = Rect::new(0, 0, 6, 1);
+        let mut buf = Buffer::empty(area);
+        let mut state = ParagraphState::default();
+
+        StatefulWidget::draw(
+            &mut Paragraph::new(text.iter()).scroll(ScrollPos::new(2, 0)),
+            area,
+            &mut buf,
+            &mut state,
+        );
+
+        // panning two columns right walks "ab" off the left edge of the text area, so the
+        // window now opens on "cdef"
+        assert_eq!(buf.get(0, 0).symbol, "c");
+        assert_eq!(buf.get(3, 0).symbol, "f");
+    }
+
+    #[test]
+    fn double_width_grapheme_straddling_the_left_edge_renders_as_blank() {
+        // a double-width glyph followed by a single-width one; scrolling by 1 column leaves the
+        // glyph half off-screen
+        let text = [Text::raw("\u{6C49}a")];
+        let area = Rect::new(0, 0, 6, 1);
+        let mut buf = Buffer::empty(area);
+        let mut state = ParagraphState::default();
+
+        StatefulWidget::draw(
+            &mut Paragraph::new(text.iter()).scroll(ScrollPos::new(1, 0)),
+            area,
+            &mut buf,
+            &mut state,
+        );
+
+        // the straddling glyph is dropped rather than drawn half off-screen, leaving the
+        // background showing through; "a" still lands at its regular post-scroll column
+        assert_eq!(buf.get(0, 0).symbol, " ");
+        assert_eq!(buf.get(1, 0).symbol, "a");
+    }
+
+    fn flatten(text: &str) -> Vec<CachedGrapheme> {
+        let mut source_line = 0usize;
+        UnicodeSegmentation::graphemes(text, true)
+            .map(|g| {
+                if g == "\n" {
+                    source_line += 1;
+                }
+                CachedGrapheme {
+                    symbol: g.to_string(),
+                    style: Style::default(),
+                    source_line,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn word_wrap_resync_covers_every_grapheme_exactly_once() {
+        let graphemes = flatten("one two three four five six");
+
+        let wrapped = compose_wrapped_lines(
+            &graphemes,
+            8,
+            Some(WrapMethod::Word { trim: false }),
+        );
+
+        // every grapheme must be covered by exactly one row, in order, with no gap or overlap —
+        // a broken ptr::eq resync (or an unflushed force-split remainder) would instead drop or
+        // duplicate a span
+        let mut covered = 0usize;
+        for line in &wrapped {
+            assert_eq!(line.start_grapheme, covered);
+            covered += line.len;
         }
+        assert_eq!(covered, graphemes.len());
+    }
+
+    #[test]
+    fn word_wrap_does_not_drop_a_force_split_tail() {
+        // the final token is wider than the row and has nothing after it — exactly the
+        // end-of-text case where a deferred remainder used to be silently dropped
+        let graphemes = flatten("ab abcdefghij");
+
+        let wrapped = compose_wrapped_lines(&graphemes, 5, Some(WrapMethod::Word { trim: false }));
+
+        let covered: usize = wrapped.iter().map(|l| l.len).sum();
+        assert_eq!(covered, graphemes.len());
+    }
+
+    #[test]
+    fn character_wrap_hard_breaks_mid_word() {
+        let graphemes = flatten("abcdefgh");
+
+        // 8 single-column graphemes at width 3 must split into fixed 3/3/2 rows regardless of
+        // word boundaries
+        let wrapped = compose_wrapped_lines(&graphemes, 3, Some(WrapMethod::Character));
+        let lens: Vec<usize> = wrapped.iter().map(|l| l.len).collect();
+        assert_eq!(lens, vec![3, 3, 2]);
+        assert_eq!(wrapped[1].start_grapheme, 3);
+    }
+
+    #[test]
+    fn character_wrap_forces_a_glyph_wider_than_the_text_area_onto_its_own_row() {
+        // a single double-width glyph doesn't fit in a 1-column area; character wrapping must
+        // still place it rather than looping forever trying to fit zero columns
+        let graphemes = vec![CachedGrapheme {
+            symbol: "\u{6F22}".to_string(),
+            style: Style::default(),
+            source_line: 0,
+        }];
+
+        let wrapped = compose_wrapped_lines(&graphemes, 1, Some(WrapMethod::Character));
+        assert_eq!(wrapped.len(), 1);
+        assert_eq!(wrapped[0].len, 1);
+    }
+
+    #[test]
+    fn character_wrap_embedded_newline_produces_no_phantom_row() {
+        // "a\nb" is two visual lines, not three: the '\n' must only terminate "a"'s row, never
+        // appear as a row of its own
+        let graphemes = flatten("a\nb");
+
+        let wrapped = compose_wrapped_lines(&graphemes, 10, Some(WrapMethod::Character));
+        assert_eq!(wrapped.len(), 2);
+        assert_eq!(wrapped[0].source_line, 0);
+        assert_eq!(wrapped[1].source_line, 1);
+        assert_eq!(wrapped[1].len, 1);
+    }
+
+    #[test]
+    fn character_wrap_genuinely_blank_line_is_a_single_blank_row() {
+        // "a\n\nb" is three visual lines: "a", a blank line, "b"
+        let graphemes = flatten("a\n\nb");
+
+        let wrapped = compose_wrapped_lines(&graphemes, 10, Some(WrapMethod::Character));
+        assert_eq!(wrapped.len(), 3);
+        assert_eq!(wrapped[1].source_line, 1);
+        assert_eq!(wrapped[1].len, 0);
+        assert_eq!(wrapped[2].source_line, 2);
     }
 }
@@ -0,0 +1,247 @@
+use unicode_width::UnicodeWidthStr;
+
+use crate::style::Style;
+
+/// A single already-flattened grapheme paired with the style it should render with, borrowed
+/// from the caller's text for the lifetime of one composer pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Styled<'a>(pub &'a str, pub Style);
+
+/// Packs a stream of [`Styled`] graphemes into fixed-width rows, one [`LineComposer::next_line`]
+/// call per row. A `'\n'` in the input always ends the current row and is never itself part of
+/// a returned row's content — a blank source line comes back as an empty row rather than being
+/// merged into its neighbor or dropped.
+pub trait LineComposer<'a> {
+    /// Returns the next row and its rendered column width, or `None` once the input and any
+    /// buffered residue are both exhausted.
+    fn next_line(&mut self) -> Option<(&[Styled<'a>], u16)>;
+}
+
+/// Pulls every symbol up to (and including) the next `'\n'` out of `symbols` into `buf`,
+/// discarding the `'\n'` itself. Returns `true` if the pull stopped on a `'\n'` (so more source
+/// lines may follow), `false` if it stopped because `symbols` is exhausted.
+fn fill_source_line<'a>(symbols: &mut dyn Iterator<Item = Styled<'a>>, buf: &mut Vec<Styled<'a>>) -> bool {
+    loop {
+        match symbols.next() {
+            Some(s) if s.0 == "\n" => return true,
+            Some(s) => buf.push(s),
+            None => return false,
+        }
+    }
+}
+
+/// Emits one row per source line verbatim, discarding whatever doesn't fit within
+/// `max_line_width` instead of wrapping it onto further rows.
+pub struct LineTruncator<'a, 'b> {
+    symbols: &'b mut dyn Iterator<Item = Styled<'a>>,
+    max_line_width: u16,
+    done: bool,
+    current_line: Vec<Styled<'a>>,
+}
+
+impl<'a, 'b> LineTruncator<'a, 'b> {
+    pub fn new(symbols: &'b mut dyn Iterator<Item = Styled<'a>>, max_line_width: u16) -> Self {
+        LineTruncator {
+            symbols,
+            max_line_width,
+            done: false,
+            current_line: Vec::new(),
+        }
+    }
+}
+
+impl<'a, 'b> LineComposer<'a> for LineTruncator<'a, 'b> {
+    fn next_line(&mut self) -> Option<(&[Styled<'a>], u16)> {
+        if self.done {
+            return None;
+        }
+
+        let mut line = Vec::new();
+        let mut col = 0u16;
+        let mut any = false;
+        let mut ended_on_newline = false;
+        while let Some(s) = self.symbols.next() {
+            any = true;
+            if s.0 == "\n" {
+                ended_on_newline = true;
+                break;
+            }
+            let width = s.0.width() as u16;
+            if col + width <= self.max_line_width {
+                col += width;
+                line.push(s);
+            }
+            // else: past max_line_width, truncated rather than wrapped
+        }
+        if !any {
+            self.done = true;
+            return None;
+        }
+        if !ended_on_newline {
+            self.done = true;
+        }
+
+        self.current_line = line;
+        Some((&self.current_line, col))
+    }
+}
+
+/// Wraps on word boundaries, falling back to an exact column split when a single token is wider
+/// than `max_line_width`. When `trim` is set, leading whitespace on a row produced by wrapping
+/// (as opposed to a row that opens a fresh source line) is dropped instead of rendered.
+pub struct WordWrapper<'a, 'b> {
+    symbols: &'b mut dyn Iterator<Item = Styled<'a>>,
+    max_line_width: u16,
+    trim: bool,
+    /// Residue of the current source line not yet placed into a returned row
+    pending: Vec<Styled<'a>>,
+    /// Whether another `fill_source_line` pull may still turn up more text
+    more_source_lines: bool,
+    /// Whether the row about to be produced opens a fresh source line (vs. continuing one that
+    /// wrapped) — only continuation rows are subject to `trim`
+    fresh_source_line: bool,
+    current_line: Vec<Styled<'a>>,
+}
+
+impl<'a, 'b> WordWrapper<'a, 'b> {
+    pub fn new(
+        symbols: &'b mut dyn Iterator<Item = Styled<'a>>,
+        max_line_width: u16,
+        trim: bool,
+    ) -> Self {
+        WordWrapper {
+            symbols,
+            max_line_width,
+            trim,
+            pending: Vec::new(),
+            more_source_lines: true,
+            fresh_source_line: true,
+            current_line: Vec::new(),
+        }
+    }
+}
+
+impl<'a, 'b> LineComposer<'a> for WordWrapper<'a, 'b> {
+    fn next_line(&mut self) -> Option<(&[Styled<'a>], u16)> {
+        if self.pending.is_empty() {
+            if !self.more_source_lines {
+                return None;
+            }
+            self.more_source_lines = fill_source_line(self.symbols, &mut self.pending);
+            self.fresh_source_line = true;
+            if !self.more_source_lines && self.pending.is_empty() {
+                return None;
+            }
+        }
+
+        if self.trim && !self.fresh_source_line {
+            while self.pending.first().map_or(false, |s| s.0 == " ") {
+                self.pending.remove(0);
+            }
+        }
+        self.fresh_source_line = false;
+
+        if self.pending.is_empty() {
+            // a blank source line, or the rest of this source line was whitespace, trimmed away
+            return Some((&[], 0));
+        }
+
+        // widest prefix that fits, which may land mid-word
+        let mut width_fit_col = 0u16;
+        let mut width_fit_split = 0usize;
+        for s in self.pending.iter() {
+            let width = s.0.width() as u16;
+            if width_fit_col + width > self.max_line_width {
+                break;
+            }
+            width_fit_col += width;
+            width_fit_split += 1;
+        }
+        if width_fit_split == 0 {
+            // not even one grapheme fits: force it through rather than stalling forever
+            width_fit_split = 1;
+            width_fit_col = self.pending[0].0.width() as u16;
+        }
+
+        let (split, col) = if width_fit_split == self.pending.len() {
+            (width_fit_split, width_fit_col)
+        } else {
+            // back off to the last space inside the fitting prefix so we don't split a word,
+            // unless that word is itself wider than the row (no space to back off to)
+            let boundary = self.pending[..width_fit_split]
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(_, s)| s.0 == " ")
+                .map(|(i, _)| i + 1);
+            match boundary {
+                Some(b) if b > 0 => {
+                    let col = self.pending[..b].iter().map(|s| s.0.width() as u16).sum();
+                    (b, col)
+                }
+                _ => (width_fit_split, width_fit_col),
+            }
+        };
+
+        self.current_line = self.pending.drain(..split).collect();
+        Some((&self.current_line, col))
+    }
+}
+
+/// Breaks at the exact column, ignoring word boundaries. A glyph wider than `max_line_width` is
+/// placed on its own row rather than looping forever.
+pub struct CharacterWrapper<'a, 'b> {
+    symbols: &'b mut dyn Iterator<Item = Styled<'a>>,
+    max_line_width: u16,
+    pending: Vec<Styled<'a>>,
+    more_source_lines: bool,
+    current_line: Vec<Styled<'a>>,
+}
+
+impl<'a, 'b> CharacterWrapper<'a, 'b> {
+    pub fn new(symbols: &'b mut dyn Iterator<Item = Styled<'a>>, max_line_width: u16) -> Self {
+        CharacterWrapper {
+            symbols,
+            max_line_width,
+            pending: Vec::new(),
+            more_source_lines: true,
+            current_line: Vec::new(),
+        }
+    }
+}
+
+impl<'a, 'b> LineComposer<'a> for CharacterWrapper<'a, 'b> {
+    fn next_line(&mut self) -> Option<(&[Styled<'a>], u16)> {
+        if self.pending.is_empty() {
+            if !self.more_source_lines {
+                return None;
+            }
+            self.more_source_lines = fill_source_line(self.symbols, &mut self.pending);
+            if !self.more_source_lines && self.pending.is_empty() {
+                return None;
+            }
+            if self.pending.is_empty() {
+                // a genuinely blank source line
+                return Some((&[], 0));
+            }
+        }
+
+        let mut col = 0u16;
+        let mut split = 0usize;
+        for s in self.pending.iter() {
+            let width = s.0.width() as u16;
+            if col + width > self.max_line_width {
+                break;
+            }
+            col += width;
+            split += 1;
+        }
+        if split == 0 {
+            split = 1;
+            col = self.pending[0].0.width() as u16;
+        }
+
+        self.current_line = self.pending.drain(..split).collect();
+        Some((&self.current_line, col))
+    }
+}